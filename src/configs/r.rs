@@ -5,6 +5,18 @@ use starship_module_config_derive::ModuleConfig;
 #[derive(Clone, ModuleConfig)]
 pub struct RConfig<'a> {
     pub format: &'a str,
+    /// Format used instead of `format` once the detected R falls short of `min_version` or its
+    /// release date predates `min_date`.
+    pub outdated_format: &'a str,
+    /// A semver requirement (e.g. `">=4.0.0"`) the detected R version must satisfy.
+    /// Leave unset to disable the version gate.
+    pub min_version: Option<&'a str>,
+    /// An ISO `YYYY-MM-DD` date the detected R build's release date must not predate.
+    /// Leave unset to disable the date gate.
+    pub min_date: Option<&'a str>,
+    pub detect_extensions: Vec<&'a str>,
+    pub detect_files: Vec<&'a str>,
+    pub detect_folders: Vec<&'a str>,
     pub disabled: bool,
 }
 
@@ -12,6 +24,12 @@ impl<'a> RootModuleConfig<'a> for RConfig<'a> {
     fn new() -> Self {
         RConfig {
             format: "via [R $version](blue bold) ",
+            outdated_format: "via [R $version](red bold) ",
+            min_version: None,
+            min_date: None,
+            detect_extensions: vec!["R", "Rproj", "Rmd", "qmd"],
+            detect_files: vec!["DESCRIPTION", "NAMESPACE", "renv.lock", ".Rprofile"],
+            detect_folders: vec![],
             disabled: false,
         }
     }
@@ -1,39 +1,59 @@
+use std::fmt;
+
 use regex::Regex;
+use semver::{Version, VersionReq};
 
 use super::{Context, Module, RootModuleConfig};
 
 use crate::configs::r::RConfig;
 use crate::formatter::StringFormatter;
 use crate::utils;
+use crate::utils::version;
 
-const R_VERSION_PATTERN: &str = r" (?P<rversion>\d+\.\d+\.\d+) ";
-
-/// Creates a module with the current Node.js version
+/// Creates a module with the current R version
 ///
-/// Will display the Node.js version if any of the following criteria are met:
-///     - Current directory contains a `.js` file
-///     - Current directory contains a `package.json` or `.node-version` file
-///     - Current directory contains a `node_modules` directory
+/// Will display the R version if any of the following criteria are met:
+///     - Current directory contains a file with an extension in `detect_extensions`
+///       (`.R`, `.Rproj`, `.Rmd`, `.qmd` by default)
+///     - Current directory contains a file in `detect_files`
+///       (`DESCRIPTION`, `NAMESPACE`, `renv.lock`, `.Rprofile` by default)
+///     - Current directory contains a folder in `detect_folders`
 pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
-    let is_r_project = context.try_begin_scan()?.set_extensions(&["R"]).is_match();
-    if !is_r_project {
+    let mut module = context.new_module("r");
+    let config: RConfig = RConfig::try_load(module.config);
+
+    if !is_r_project(context, &config) {
         log::debug!("r: Not a R project; getting out!");
         return None;
     }
 
     log::debug!("r: This is a R project; getting in...");
 
-    let r_version = utils::exec_cmd("r", &["--version"])?.stderr;
-    log::debug!("r: r_version={}", r_version);
+    let r_version_output = utils::exec_cmd("r", &["--version"])?.stderr;
+    log::debug!("r: r_version_output={}", r_version_output);
 
-    let formatted_version = parse_version(&r_version)?;
+    let (r_version, formatted_version) = parse_version(&r_version_output)?;
     log::debug!("r: formatted_version={}", formatted_version);
 
-    let mut module = context.new_module("r");
-    let config: RConfig = RConfig::try_load(module.config);
-    let formatter = if let Ok(formatter) = StringFormatter::new(config.format) {
+    let r_date = parse_date(&r_version_output);
+    log::debug!("r: r_date={:?}", r_date);
+
+    let r_channel = parse_channel(&r_version_output);
+    log::debug!("r: r_channel={}", r_channel);
+
+    let is_outdated = is_version_outdated(&r_version, config.min_version)
+        || is_date_outdated(r_date.as_deref(), config.min_date);
+    let format_string = if is_outdated {
+        config.outdated_format
+    } else {
+        config.format
+    };
+
+    let formatter = if let Ok(formatter) = StringFormatter::new(format_string) {
         formatter.map(|variable| match variable {
             "version" => Some(formatted_version.clone()),
+            "date" => r_date.clone(),
+            "channel" => Some(r_channel.to_string()),
             _ => {
                 log::debug!("r: No version for R has been detected.");
                 None
@@ -50,22 +70,134 @@ pub fn module<'a>(context: &'a Context) -> Option<Module<'a>> {
     Some(module)
 }
 
-fn parse_version(version: &str) -> Option<String> {
-    let version_regex = Regex::new(R_VERSION_PATTERN).ok()?;
-    let captures = version_regex.captures(version)?;
-    let r_version = captures["rversion"].to_owned();
-    let r_formatted = format!("{}{}", "v", r_version);
+/// Whether the scanned directory matches any of `config`'s `detect_extensions`,
+/// `detect_files` or `detect_folders` markers. Split out of `module()` so detection can be
+/// exercised without shelling out to `r --version`.
+fn is_r_project(context: &Context, config: &RConfig<'_>) -> bool {
+    context
+        .try_begin_scan()
+        .map(|scan_dir| {
+            scan_dir
+                .set_extensions(&config.detect_extensions)
+                .set_files(&config.detect_files)
+                .set_folders(&config.detect_folders)
+                .is_match()
+        })
+        .unwrap_or(false)
+}
+
+fn parse_version(output: &str) -> Option<(Version, String)> {
+    let (r_version, display) = version::parse_version(output)?;
     log::debug!("r: r_version = {}", r_version);
-    Some(r_formatted.trim().to_owned())
+    let formatted_version = format!("v{}", display);
+    Some((r_version, formatted_version))
+}
+
+/// Whether `r_version` fails to satisfy `min_version`. A missing or unparsable `min_version`
+/// never counts as outdated; a malformed requirement is logged and otherwise ignored.
+fn is_version_outdated(r_version: &Version, min_version: Option<&str>) -> bool {
+    let min_version = match min_version {
+        Some(min_version) => min_version,
+        None => return false,
+    };
+
+    match VersionReq::parse(min_version) {
+        Ok(req) => !req.matches(r_version),
+        Err(error) => {
+            log::warn!(
+                "r: `min_version` {:?} is not a valid semver requirement: {}",
+                min_version,
+                error
+            );
+            false
+        }
+    }
+}
+
+/// Extracts the `(YYYY-MM-DD)` release date embedded in R's `--version` banner, e.g. the
+/// `2020-02-29` in `R version 3.6.3 (2020-02-29)`. Non-stable builds pack an SVN revision into
+/// the same parentheses (`R version 4.1.0 Patched (2021-05-28 r80324)`), so the date isn't
+/// anchored to the closing paren. Builds without a parenthesized date yield `None`.
+fn parse_date(output: &str) -> Option<String> {
+    let date_regex = Regex::new(r"\((\d{4}-\d{2}-\d{2})(?:\s+\S+)?\)").ok()?;
+    let captures = date_regex.captures(output)?;
+    Some(captures[1].to_owned())
+}
+
+/// Whether `r_date` predates `min_date`. Both are `YYYY-MM-DD` strings, compared field-by-field
+/// as integers so no date-handling crate is required. A missing date on either side (no
+/// `min_date` configured, or the build didn't report one) never counts as outdated.
+fn is_date_outdated(r_date: Option<&str>, min_date: Option<&str>) -> bool {
+    let r_date = r_date.and_then(parse_date_fields);
+    let min_date = min_date.and_then(parse_date_fields);
+    match (r_date, min_date) {
+        (Some(r_date), Some(min_date)) => r_date < min_date,
+        _ => false,
+    }
+}
+
+fn parse_date_fields(date: &str) -> Option<(u32, u32, u32)> {
+    let mut fields = date.splitn(3, '-');
+    let year = fields.next()?.parse().ok()?;
+    let month = fields.next()?.parse().ok()?;
+    let day = fields.next()?.parse().ok()?;
+    Some((year, month, day))
+}
+
+/// The release channel of an R build, following the same stable/beta/nightly-style distinction
+/// the rustc module draws for rustc toolchains.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Channel {
+    Release,
+    Patched,
+    Devel,
+    Alpha,
+    Beta,
+    Rc,
+}
+
+impl fmt::Display for Channel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Channel::Release => "release",
+            Channel::Patched => "patched",
+            Channel::Devel => "devel",
+            Channel::Alpha => "alpha",
+            Channel::Beta => "beta",
+            Channel::Rc => "rc",
+        })
+    }
+}
+
+/// Classifies R's `--version` banner into a [`Channel`]. Defaults to `Channel::Release` when
+/// none of the known markers (`Patched`, `Under development`/`unstable`, `alpha`, `beta`, `RC`)
+/// are present.
+fn parse_channel(output: &str) -> Channel {
+    let lower = output.to_lowercase();
+    if lower.contains("under development") || lower.contains("unstable") {
+        Channel::Devel
+    } else if lower.contains("patched") {
+        Channel::Patched
+    } else if lower.contains("alpha") {
+        Channel::Alpha
+    } else if lower.contains("beta") {
+        Channel::Beta
+    } else if Regex::new(r"(?i)\brc\b")
+        .map(|re| re.is_match(output))
+        .unwrap_or(false)
+    {
+        Channel::Rc
+    } else {
+        Channel::Release
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    // use crate::modules::utils::test::render_module;
-    // use ansi_term::Color;
-    // use std::fs::{self, File};
-    // use std::io;
-    // use tempfile;
+    use crate::context::Context;
+    use crate::modules::utils::test::render_module;
+    use std::fs::{self, File};
+    use std::io;
     use super::*;
 
     #[test]
@@ -73,26 +205,303 @@ mod tests {
         let r_v3 = r#"r_version=R version 3.6.3 (2020-02-29) -- "Holding the Windsock"
         Copyright (C) 2020 The R Foundation for Statistical Computing
         Platform: x86_64-w64-mingw32/x64 (64-bit)
-        
+
         R is free software and comes with ABSOLUTELY NO WARRANTY.
         You are welcome to redistribute it under the terms of the
         GNU General Public License versions 2 or 3.
         For more information about these matters see
         https://www.gnu.org/licenses/."#;
-        assert_eq!(parse_version(r_v3), Some(String::from("v3.6.3")));
+        let (version, formatted_version) = parse_version(r_v3).unwrap();
+        assert_eq!(version, Version::new(3, 6, 3));
+        assert_eq!(formatted_version, "v3.6.3");
     }
 
     #[test]
-    fn test_parse_r_invalid_semantic_version() {
+    fn test_parse_r_four_component_version() {
         let r_invalid = r#"r_version=R version 3.6.5.2 (2020-02-29) -- "Holding the Windsock"
         Copyright (C) 2020 The R Foundation for Statistical Computing
         Platform: x86_64-w64-mingw32/x64 (64-bit)
-        
+
         R is free software and comes with ABSOLUTELY NO WARRANTY.
         You are welcome to redistribute it under the terms of the
         GNU General Public License versions 2 or 3.
         For more information about these matters see
         https://www.gnu.org/licenses/."#;
-        assert_eq!(parse_version(r_invalid), None);
+        let (_, formatted_version) = parse_version(r_invalid).unwrap();
+        assert_eq!(formatted_version, "v3.6.5.2");
+    }
+
+    #[test]
+    fn test_parse_r_version_with_no_digits() {
+        assert_eq!(parse_version("not a version at all"), None);
+    }
+
+    #[test]
+    fn min_version_satisfied_is_not_outdated() {
+        let r_version = Version::new(4, 1, 0);
+        assert!(!is_version_outdated(&r_version, Some(">=4.0.0")));
+    }
+
+    #[test]
+    fn min_version_unsatisfied_is_outdated() {
+        let r_version = Version::new(3, 6, 3);
+        assert!(is_version_outdated(&r_version, Some(">=4.0.0")));
+    }
+
+    #[test]
+    fn malformed_min_version_falls_back_to_not_outdated() {
+        let r_version = Version::new(3, 6, 3);
+        assert!(!is_version_outdated(&r_version, Some("not a requirement")));
+    }
+
+    #[test]
+    fn unset_min_version_is_never_outdated() {
+        let r_version = Version::new(3, 6, 3);
+        assert!(!is_version_outdated(&r_version, None));
+    }
+
+    #[test]
+    fn test_parse_date() {
+        let r_v3 = r#"R version 3.6.3 (2020-02-29) -- "Holding the Windsock""#;
+        assert_eq!(parse_date(r_v3), Some(String::from("2020-02-29")));
+    }
+
+    #[test]
+    fn test_parse_date_missing() {
+        let r_devel = "R Under development (unstable)";
+        assert_eq!(parse_date(r_devel), None);
+    }
+
+    #[test]
+    fn test_parse_date_with_trailing_revision() {
+        let r_patched = "R version 4.1.0 Patched (2021-05-28 r80324)";
+        assert_eq!(parse_date(r_patched), Some(String::from("2021-05-28")));
+
+        let r_devel = "R Under development (unstable) (2021-06-01 r80404)";
+        assert_eq!(parse_date(r_devel), Some(String::from("2021-06-01")));
+    }
+
+    #[test]
+    fn min_date_satisfied_is_not_outdated() {
+        assert!(!is_date_outdated(Some("2020-02-29"), Some("2018-01-01")));
+    }
+
+    #[test]
+    fn min_date_unsatisfied_is_outdated() {
+        assert!(is_date_outdated(Some("2015-01-01"), Some("2018-01-01")));
+    }
+
+    #[test]
+    fn missing_date_is_never_outdated() {
+        assert!(!is_date_outdated(None, Some("2018-01-01")));
+    }
+
+    #[test]
+    fn unset_min_date_is_never_outdated() {
+        assert!(!is_date_outdated(Some("2015-01-01"), None));
+    }
+
+    #[test]
+    fn test_parse_channel_release() {
+        let r_release = r#"R version 3.6.3 (2020-02-29) -- "Holding the Windsock""#;
+        assert_eq!(parse_channel(r_release), Channel::Release);
+    }
+
+    #[test]
+    fn test_parse_channel_patched() {
+        let r_patched = "R version 4.1.0 Patched (2021-05-28 r80324)";
+        assert_eq!(parse_channel(r_patched), Channel::Patched);
+    }
+
+    #[test]
+    fn test_parse_channel_devel() {
+        let r_devel = "R Under development (unstable) (2021-06-01 r80404)";
+        assert_eq!(parse_channel(r_devel), Channel::Devel);
+    }
+
+    #[test]
+    fn test_parse_channel_alpha() {
+        let r_alpha = "R version 4.2.0 alpha (2022-03-21 r81954)";
+        assert_eq!(parse_channel(r_alpha), Channel::Alpha);
+    }
+
+    #[test]
+    fn test_parse_channel_beta() {
+        let r_beta = "R version 4.2.0 beta (2022-04-04 r82053)";
+        assert_eq!(parse_channel(r_beta), Channel::Beta);
+    }
+
+    #[test]
+    fn test_parse_channel_rc() {
+        let r_rc = "R version 4.1.0 RC (2021-05-15 r80300)";
+        assert_eq!(parse_channel(r_rc), Channel::Rc);
+    }
+
+    fn is_r_project_for(dir: &std::path::Path, config: RConfig<'_>) -> bool {
+        let context = Context::new_with_dir(Default::default(), dir);
+        is_r_project(&context, &config)
+    }
+
+    #[test]
+    fn detects_r_extension() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("script.R"))?;
+        let actual = is_r_project_for(dir.path(), RConfig::new());
+        dir.close()?;
+        assert!(actual);
+        Ok(())
+    }
+
+    #[test]
+    fn detects_rproj_extension() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("project.Rproj"))?;
+        let actual = is_r_project_for(dir.path(), RConfig::new());
+        dir.close()?;
+        assert!(actual);
+        Ok(())
+    }
+
+    #[test]
+    fn detects_rmd_extension() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("report.Rmd"))?;
+        let actual = is_r_project_for(dir.path(), RConfig::new());
+        dir.close()?;
+        assert!(actual);
+        Ok(())
+    }
+
+    #[test]
+    fn detects_qmd_extension() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("report.qmd"))?;
+        let actual = is_r_project_for(dir.path(), RConfig::new());
+        dir.close()?;
+        assert!(actual);
+        Ok(())
+    }
+
+    #[test]
+    fn detects_description_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("DESCRIPTION"))?;
+        let actual = is_r_project_for(dir.path(), RConfig::new());
+        dir.close()?;
+        assert!(actual);
+        Ok(())
+    }
+
+    #[test]
+    fn detects_namespace_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("NAMESPACE"))?;
+        let actual = is_r_project_for(dir.path(), RConfig::new());
+        dir.close()?;
+        assert!(actual);
+        Ok(())
+    }
+
+    #[test]
+    fn detects_renv_lock_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("renv.lock"))?;
+        let actual = is_r_project_for(dir.path(), RConfig::new());
+        dir.close()?;
+        assert!(actual);
+        Ok(())
+    }
+
+    #[test]
+    fn detects_rprofile_file() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join(".Rprofile"))?;
+        let actual = is_r_project_for(dir.path(), RConfig::new());
+        dir.close()?;
+        assert!(actual);
+        Ok(())
+    }
+
+    #[test]
+    fn folder_without_any_r_marker_is_not_detected() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("README.md"))?;
+        let actual = is_r_project_for(dir.path(), RConfig::new());
+        dir.close()?;
+        assert!(!actual);
+        Ok(())
+    }
+
+    #[test]
+    fn custom_detect_extensions_replaces_the_default() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("script.R"))?;
+        let config = RConfig {
+            detect_extensions: vec!["txt"],
+            detect_files: vec![],
+            detect_folders: vec![],
+            ..RConfig::new()
+        };
+
+        // A plain `.R` file no longer matches once `detect_extensions` is overridden away from it.
+        let actual = is_r_project_for(dir.path(), config);
+        dir.close()?;
+        assert!(!actual);
+        Ok(())
+    }
+
+    #[test]
+    fn custom_detect_extensions_takes_effect() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("notes.txt"))?;
+        let config = RConfig {
+            detect_extensions: vec!["txt"],
+            detect_files: vec![],
+            detect_folders: vec![],
+            ..RConfig::new()
+        };
+
+        let actual = is_r_project_for(dir.path(), config);
+        dir.close()?;
+        assert!(actual);
+        Ok(())
+    }
+
+    #[test]
+    fn custom_detect_folders_takes_effect() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        fs::create_dir(dir.path().join("r_pkg"))?;
+
+        let not_detected = is_r_project_for(dir.path(), RConfig::new());
+        assert!(!not_detected);
+
+        let config = RConfig {
+            detect_extensions: vec![],
+            detect_files: vec![],
+            detect_folders: vec!["r_pkg"],
+            ..RConfig::new()
+        };
+        let detected = is_r_project_for(dir.path(), config);
+
+        dir.close()?;
+        assert!(detected);
+        Ok(())
+    }
+
+    #[test]
+    fn renders_when_r_is_installed() -> io::Result<()> {
+        if utils::exec_cmd("r", &["--version"]).is_none() {
+            // No R binary on PATH in this environment; nothing further to exercise here.
+            return Ok(());
+        }
+
+        let dir = tempfile::tempdir()?;
+        File::create(dir.path().join("DESCRIPTION"))?;
+
+        let actual = render_module("r", dir.path());
+
+        dir.close()?;
+        assert!(actual.is_some());
+        Ok(())
     }
 }
@@ -0,0 +1,64 @@
+use std::io::Error;
+use std::process::{Command, Output, Stdio};
+
+pub mod version;
+
+/// Return the results of a command's output, or `None` if the command does not exist or exits
+/// unsuccessfully.
+pub fn exec_cmd(cmd: &str, args: &[&str]) -> Option<CommandOutput> {
+    internal_exec_cmd(cmd, args)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandOutput {
+    pub stdout: String,
+    pub stderr: String,
+}
+
+fn internal_exec_cmd(cmd: &str, args: &[&str]) -> Option<CommandOutput> {
+    log::trace!("Executing command {:?} with args {:?}", cmd, args);
+
+    let full_path = match which::which(cmd) {
+        Ok(full_path) => full_path,
+        Err(e) => {
+            log::trace!("Unable to find full path to command {:?}: {}", cmd, e);
+            return None;
+        }
+    };
+
+    let output = Command::new(full_path)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output();
+
+    extract_cmd_output(output)
+}
+
+fn extract_cmd_output(output: Result<Output, Error>) -> Option<CommandOutput> {
+    match output {
+        Ok(output) => {
+            let stdout_string = String::from_utf8(output.stdout).ok()?;
+            let stderr_string = String::from_utf8(output.stderr).ok()?;
+            log::trace!(
+                "stdout: {:?}, stderr: {:?}",
+                stdout_string,
+                stderr_string
+            );
+
+            if !output.status.success() {
+                return None;
+            }
+
+            Some(CommandOutput {
+                stdout: stdout_string,
+                stderr: stderr_string,
+            })
+        }
+        Err(error) => {
+            log::debug!("Executing command failed: {:?}", error);
+            None
+        }
+    }
+}
@@ -0,0 +1,71 @@
+use regex::Regex;
+use semver::{BuildMetadata, Prerelease, Version};
+
+/// Matches the first dotted numeric run in a command's output, e.g. the `3.6.5.2` in
+/// `R version 3.6.5.2 (2020-02-29)`, optionally followed by a `-prerelease` suffix.
+const VERSION_TOKEN_PATTERN: &str = r"\d+(?:\.\d+)+(?:-\S*)?";
+
+/// Find the first semver-looking token in `output` and normalize it into a full
+/// `major.minor.patch` [`Version`].
+///
+/// Missing components are padded with zeroes (`3.6` becomes `3.6.0`), and any numeric
+/// component past the third is folded into the version's build metadata
+/// (`3.6.5.2` becomes `3.6.5+2`). Returns the parsed version alongside the raw token that was
+/// matched, so callers can still show users exactly what their tool reported.
+///
+/// Returns `None` if no digits resembling a version can be found at all.
+pub fn parse_version(output: &str) -> Option<(Version, String)> {
+    let pattern = Regex::new(VERSION_TOKEN_PATTERN).ok()?;
+    let token = pattern.find(output)?.as_str();
+
+    let (numeric, pre) = match token.find('-') {
+        Some(idx) => (&token[..idx], Some(&token[idx + 1..])),
+        None => (token, None),
+    };
+
+    let mut parts = numeric.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+    let patch = parts.next().and_then(|s| s.parse().ok()).unwrap_or(0);
+    let build: Vec<&str> = parts.collect();
+
+    let mut version = Version::new(major, minor, patch);
+    if let Some(pre) = pre {
+        version.pre = Prerelease::new(pre).unwrap_or(Prerelease::EMPTY);
+    }
+    if !build.is_empty() {
+        version.build = BuildMetadata::new(&build.join(".")).unwrap_or(BuildMetadata::EMPTY);
+    }
+
+    Some((version, token.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_a_full_semantic_version() {
+        let (version, display) = parse_version("R version 3.6.3 (2020-02-29)").unwrap();
+        assert_eq!(version, Version::new(3, 6, 3));
+        assert_eq!(display, "3.6.3");
+    }
+
+    #[test]
+    fn pads_a_two_component_version() {
+        let (version, _) = parse_version("go1.16 linux/amd64").unwrap();
+        assert_eq!(version, Version::new(1, 16, 0));
+    }
+
+    #[test]
+    fn folds_trailing_components_into_build_metadata() {
+        let (version, display) = parse_version("R version 3.6.5.2 (2020-02-29)").unwrap();
+        assert_eq!(version.to_string(), "3.6.5+2");
+        assert_eq!(display, "3.6.5.2");
+    }
+
+    #[test]
+    fn returns_none_without_any_digits() {
+        assert_eq!(parse_version("not a version at all"), None);
+    }
+}